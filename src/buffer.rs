@@ -0,0 +1,47 @@
+use rayon::prelude::*;
+
+/// Two `Vec<T>` buffers with a front/back role that flips every step,
+/// instead of allocating (and cloning) a fresh `Vec` on every tick.
+pub struct DoubleBuffer<T> {
+    buffers: [Vec<T>; 2],
+    switch: bool,
+}
+impl<T: Clone> DoubleBuffer<T> {
+    pub fn new(initial: Vec<T>) -> Self {
+        let back = initial.clone();
+        Self {
+            buffers: [initial, back],
+            switch: false,
+        }
+    }
+}
+impl<T> DoubleBuffer<T> {
+    pub fn front(&self) -> &Vec<T> {
+        if self.switch {
+            &self.buffers[1]
+        } else {
+            &self.buffers[0]
+        }
+    }
+    fn split_mut(&mut self) -> (&Vec<T>, &mut Vec<T>) {
+        let (b0, b1) = self.buffers.split_at_mut(1);
+        if self.switch {
+            (&b1[0], &mut b0[0])
+        } else {
+            (&b0[0], &mut b1[0])
+        }
+    }
+    /// Fills the back buffer in parallel via `f(index, front)`, then flips
+    /// `front`/`back` so the freshly written buffer becomes the new front.
+    pub fn step<F>(&mut self, f: F)
+    where
+        T: Send + Sync,
+        F: Fn(usize, &Vec<T>) -> T + Sync,
+    {
+        let (front, back) = self.split_mut();
+        back.par_iter_mut()
+            .enumerate()
+            .for_each(|(i, cell)| *cell = f(i, front));
+        self.switch = !self.switch;
+    }
+}
@@ -1,10 +1,36 @@
 use rand::Rng;
 use rayon::prelude::*;
 use std::convert::TryInto;
+use std::path::{Path, PathBuf};
+
+mod buffer;
+mod pattern;
+mod rule;
+mod sparse;
+use buffer::DoubleBuffer;
+pub use pattern::PatternError;
+pub use rule::Rule;
+pub use sparse::{SparseGame, SparseGrid};
+
+/// Decodes a pattern file into the set of live coordinates a `SparseGame`
+/// works with, placed within a `width x height` viewport. The pattern is
+/// centered when `center` is `true`, otherwise placed in the top-left
+/// corner.
+pub fn load_sparse_pattern(
+    path: &Path,
+    width: usize,
+    height: usize,
+    center: bool,
+) -> Result<SparseGrid, PatternError> {
+    pattern::load_cells(path, width, height, center)
+}
 
 pub enum GridInitialization {
     Random(f64),
     Custom(Grid),
+    /// Seeds the grid from a pattern file, centered when `center` is `true`
+    /// and placed in the top-left corner otherwise.
+    FromPattern(PathBuf, bool),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -20,7 +46,9 @@ pub enum DeathState {
 }
 #[derive(Clone, Debug, PartialEq)]
 pub enum Cell {
-    Alive(LivingState),
+    /// `age` is the number of consecutive ticks the cell has stayed alive;
+    /// it resets to `0` on birth.
+    Alive(LivingState, u8),
     Dead(DeathState),
 }
 type Grid = Vec<Cell>;
@@ -28,7 +56,9 @@ type Grid = Vec<Cell>;
 pub struct Game {
     height: usize,
     width: usize,
-    grid: Grid,
+    buffer: DoubleBuffer<Cell>,
+    rule: Rule,
+    wrap: bool,
 }
 impl Game {
     fn init_rand(width: usize, height: usize, probability: f64) -> Grid {
@@ -36,66 +66,85 @@ impl Game {
             .into_par_iter()
             .map(|_| {
                 if rand::thread_rng().gen_bool(probability) {
-                    Cell::Alive(LivingState::Remains)
+                    Cell::Alive(LivingState::Remains, 0)
                 } else {
                     Cell::Dead(DeathState::Remains)
                 }
             })
             .collect()
     }
-    fn live_neighbour_count1(&self, row: usize, col: usize) -> u8 {
+    fn live_neighbour_count1(grid: &[Cell], width: usize, height: usize, row: usize, col: usize) -> u8 {
         let mut count = 0;
         if col > 0 {
             if row > 0 {
                 // nw
-                if let Some(&Cell::Alive(_)) = self.grid.get((row - 1) * self.width + col - 1) {
+                if let Some(&Cell::Alive(_, _)) = grid.get((row - 1) * width + col - 1) {
                     count += 1
                 }
             }
             // w
-            if let Some(&Cell::Alive(_)) = self.grid.get(row * self.width + (col - 1)) {
+            if let Some(&Cell::Alive(_, _)) = grid.get(row * width + (col - 1)) {
                 count += 1
             }
-            if row < self.height - 1 {
+            if row < height - 1 {
                 // sw
-                if let Some(&Cell::Alive(_)) = self.grid.get((row + 1) * self.width + (col - 1)) {
+                if let Some(&Cell::Alive(_, _)) = grid.get((row + 1) * width + (col - 1)) {
                     count += 1
                 }
             }
         }
-        if col < self.width - 1 {
+        if col < width - 1 {
             if row > 0 {
                 // ne
-                if let Some(&Cell::Alive(_)) = self.grid.get((row - 1) * self.width + (col + 1)) {
+                if let Some(&Cell::Alive(_, _)) = grid.get((row - 1) * width + (col + 1)) {
                     count += 1
                 }
             }
             // e
-            if let Some(&Cell::Alive(_)) = self.grid.get(row * self.width + (col + 1)) {
+            if let Some(&Cell::Alive(_, _)) = grid.get(row * width + (col + 1)) {
                 count += 1
             }
-            if row < self.height - 1 {
+            if row < height - 1 {
                 // se
-                if let Some(&Cell::Alive(_)) = self.grid.get((row + 1) * self.width + (col + 1)) {
+                if let Some(&Cell::Alive(_, _)) = grid.get((row + 1) * width + (col + 1)) {
                     count += 1
                 }
             }
         }
-        if row < self.height - 1 {
+        if row < height - 1 {
             // s
-            if let Some(&Cell::Alive(_)) = self.grid.get((row + 1) * self.width + col) {
+            if let Some(&Cell::Alive(_, _)) = grid.get((row + 1) * width + col) {
                 count += 1
             }
         }
         if row > 0 {
             // n
-            if let Some(&Cell::Alive(_)) = self.grid.get((row - 1) * self.width + col) {
+            if let Some(&Cell::Alive(_, _)) = grid.get((row - 1) * width + col) {
                 count += 1
             }
         }
         count
     }
-    fn _live_neighbour_count2(&self, row: usize, col: usize) -> u8 {
+    /// Same as `live_neighbour_count1`, but wraps lookups around the grid's
+    /// edges (column `-1` maps to `width-1`, row `height` maps to `0`) for
+    /// toroidal mode.
+    fn live_neighbour_count_wrap(grid: &[Cell], width: usize, height: usize, row: usize, col: usize) -> u8 {
+        let mut count = 0;
+        for dy in [-1i64, 0, 1].iter() {
+            for dx in [-1i64, 0, 1].iter() {
+                if *dx == 0 && *dy == 0 {
+                    continue;
+                }
+                let n_row = (row as i64 + dy).rem_euclid(height as i64) as usize;
+                let n_col = (col as i64 + dx).rem_euclid(width as i64) as usize;
+                if let Some(&Cell::Alive(_, _)) = grid.get(n_row * width + n_col) {
+                    count += 1
+                }
+            }
+        }
+        count
+    }
+    fn _live_neighbour_count2(grid: &[Cell], width: usize, row: usize, col: usize) -> u8 {
         let mut count = 0;
         for i in [-1, 0, 1].iter() {
             if let Ok(n_row) = TryInto::<usize>::try_into((row as i32) + i) {
@@ -104,7 +153,7 @@ impl Game {
                         continue;
                     }
                     if let Ok(n_col) = TryInto::<usize>::try_into((col as i32) + j) {
-                        if let Some(&Cell::Alive(_)) = self.grid.get(n_row * self.width + n_col) {
+                        if let Some(&Cell::Alive(_, _)) = grid.get(n_row * width + n_col) {
                             count += 1
                         }
                     } else {
@@ -117,37 +166,13 @@ impl Game {
         }
         count
     }
-    fn compute_next(&self) -> Grid {
-        let next_grid = self.grid.clone();
-        next_grid
-            .into_par_iter()
-            .enumerate()
-            .map(|(x, cell)| {
-                let i = x / self.width;
-                let j = x % self.width;
-                let neighbour_count = self.live_neighbour_count1(i, j);
-                let alive = if let Cell::Alive(_) = cell {
-                    true
-                } else {
-                    false
-                };
-                match neighbour_count {
-                    0..=1 if alive => Cell::Dead(DeathState::Underpopulation),
-                    4..=8 if alive => Cell::Dead(DeathState::Overpopulation),
-                    3 if !alive => Cell::Alive(LivingState::Reproduction),
-                    _ => match cell {
-                        Cell::Alive(LivingState::Reproduction) => Cell::Alive(LivingState::Remains),
-                        Cell::Dead(DeathState::Overpopulation)
-                        | Cell::Dead(DeathState::Underpopulation) => {
-                            Cell::Dead(DeathState::Remains)
-                        }
-                        _ => cell,
-                    },
-                }
-            })
-            .collect()
-    }
-    pub fn new(width: usize, height: usize, init: GridInitialization) -> Self {
+    pub fn new(
+        width: usize,
+        height: usize,
+        init: GridInitialization,
+        rule: Rule,
+        wrap: bool,
+    ) -> Self {
         let grid = match init {
             GridInitialization::Random(probability) => Self::init_rand(width, height, probability),
             GridInitialization::Custom(grid) => {
@@ -158,19 +183,57 @@ impl Game {
                 );
                 grid
             }
+            GridInitialization::FromPattern(path, center) => {
+                pattern::load_into_grid(&path, width, height, center)
+                    .expect("couldn't load pattern file")
+            }
         };
         Self {
             width,
             height,
-            grid,
+            buffer: DoubleBuffer::new(grid),
+            rule,
+            wrap,
         }
     }
     pub fn get_grid(&self) -> &Grid {
-        &self.grid
+        self.buffer.front()
     }
     pub fn tick(&mut self) {
-        let next_grid = self.compute_next();
-        self.grid = next_grid;
+        let width = self.width;
+        let height = self.height;
+        let rule = self.rule;
+        let wrap = self.wrap;
+        self.buffer.step(|x, front| {
+            let i = x / width;
+            let j = x % width;
+            let neighbour_count = if wrap {
+                Self::live_neighbour_count_wrap(front, width, height, i, j)
+            } else {
+                Self::live_neighbour_count1(front, width, height, i, j)
+            };
+            let cell = &front[x];
+            let alive = if let Cell::Alive(_, _) = cell {
+                true
+            } else {
+                false
+            };
+            match neighbour_count {
+                n if alive && !rule.will_survive(n) => Cell::Dead(rule.death_state(n)),
+                n if !alive && rule.will_be_born(n) => Cell::Alive(LivingState::Reproduction, 0),
+                _ => match cell {
+                    Cell::Alive(LivingState::Reproduction, age) => {
+                        Cell::Alive(LivingState::Remains, age + 1)
+                    }
+                    Cell::Alive(LivingState::Remains, age) => {
+                        Cell::Alive(LivingState::Remains, age.saturating_add(1))
+                    }
+                    Cell::Dead(DeathState::Overpopulation)
+                    | Cell::Dead(DeathState::Underpopulation) => Cell::Dead(DeathState::Remains),
+                    _ => cell.clone(),
+                },
+            }
+        });
     }
 }
 
@@ -189,7 +252,7 @@ fn test_game_rules() {
         .map(|(x, _)| {
             let xy = (x % width, x / width);
             if xy == (1, 0) || xy == (2, 1) || xy == (0, 2) || xy == (1, 2) || xy == (2, 2) {
-                Cell::Alive(LivingState::Remains)
+                Cell::Alive(LivingState::Remains, 0)
             } else {
                 Cell::Dead(DeathState::Remains)
             }
@@ -206,9 +269,9 @@ fn test_game_rules() {
         .map(|(x, _)| {
             let xy = (x % width, x / width);
             if xy == (2, 1) || xy == (1, 2) || xy == (2, 2) {
-                Cell::Alive(LivingState::Remains)
+                Cell::Alive(LivingState::Remains, 1)
             } else if xy == (0, 1) || xy == (1, 3) {
-                Cell::Alive(LivingState::Reproduction)
+                Cell::Alive(LivingState::Reproduction, 0)
             } else if xy == (1, 0) || xy == (0, 2) {
                 Cell::Dead(DeathState::Underpopulation)
             } else {
@@ -216,7 +279,53 @@ fn test_game_rules() {
             }
         })
         .collect();
-    let mut game = Game::new(width, height, GridInitialization::Custom(start_grid));
+    let mut game = Game::new(
+        width,
+        height,
+        GridInitialization::Custom(start_grid),
+        Rule::conway(),
+        false,
+    );
     game.tick();
     assert_eq!(&next_grid, game.get_grid());
 }
+
+#[test]
+fn test_game_tick_wrap() {
+    let width: usize = 3;
+    let height: usize = 3;
+    // Live cells confined to the grid's corners; without wrap they'd be
+    // mostly isolated, but on a 3x3 torus every cell is a neighbour of
+    // every other cell, so wrap should let them populate the whole grid.
+    let start_grid: Grid = (0..width * height)
+        .map(|x| {
+            let xy = (x % width, x / width);
+            if xy == (0, 0) || xy == (2, 0) || xy == (0, 2) {
+                Cell::Alive(LivingState::Remains, 0)
+            } else {
+                Cell::Dead(DeathState::Remains)
+            }
+        })
+        .collect();
+    let mut game = Game::new(
+        width,
+        height,
+        GridInitialization::Custom(start_grid),
+        Rule::conway(),
+        true,
+    );
+    game.tick();
+    let grid = game.get_grid();
+    for x in 0..width * height {
+        let xy = (x % width, x / width);
+        // The three corners had 2 live neighbours (the other two corners)
+        // and survive; every other cell had 3 (all three corners) and is
+        // born.
+        let expected = if xy == (0, 0) || xy == (2, 0) || xy == (0, 2) {
+            Cell::Alive(LivingState::Remains, 1)
+        } else {
+            Cell::Alive(LivingState::Reproduction, 0)
+        };
+        assert_eq!(grid[x], expected);
+    }
+}
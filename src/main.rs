@@ -1,27 +1,46 @@
 use clap::{crate_version, App, Arg};
 use crossterm::{
     cursor,
+    event::{self, Event, KeyCode},
     style::{Colorize, PrintStyledContent},
     terminal, QueueableCommand,
 };
-use game_of_life::{Cell, DeathState, Game, GridInitialization, LivingState};
+use game_of_life::{
+    load_sparse_pattern, Cell, DeathState, Game, GridInitialization, LivingState, Rule, SparseGame,
+};
 use std::{
     cmp::min,
     env,
     io::{stdout, Write},
+    path::PathBuf,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
-    thread, time,
+    time,
 };
 
+const DELAY_STEP: time::Duration = time::Duration::from_millis(10);
+const MIN_DELAY: time::Duration = time::Duration::from_millis(1);
+
 const OPTION_WIDTH: &str = "width";
 const OPTION_HEIGHT: &str = "height";
 const OPTION_DELAY: &str = "delay";
 const OPTION_CELL_PROBABILITY: &str = "cell_probability";
 const OPTION_DRAW_META_STATE: &str = "draw_meta_state";
 const OPTION_STATISTICS: &str = "statistics";
+const OPTION_BACKEND: &str = "backend";
+const OPTION_PATTERN_FILE: &str = "pattern_file";
+const OPTION_PATTERN_TOP_LEFT: &str = "pattern_top_left";
+const OPTION_RULE: &str = "rule";
+const OPTION_WRAP: &str = "wrap";
+const OPTION_HEATMAP: &str = "heatmap";
+
+#[derive(Copy, Clone)]
+enum Backend {
+    Dense,
+    Sparse,
+}
 
 struct Config {
     width: usize,
@@ -30,6 +49,23 @@ struct Config {
     probability: f64,
     draw_meta_state: bool,
     statistics: bool,
+    backend: Backend,
+    pattern_file: Option<PathBuf>,
+    pattern_center: bool,
+    rule: Rule,
+    wrap: bool,
+    heatmap: bool,
+}
+
+/// Maps a cell's age (consecutive ticks alive) to a colour: fresh births are
+/// bright, long-lived still-lifes and oscillators fade towards dim blue.
+fn heatmap_symbol(age: u8) -> crossterm::style::StyledContent<&'static str> {
+    match age {
+        0..=1 => "◼".white(),
+        2..=5 => "◼".grey(),
+        6..=15 => "◼".dark_grey(),
+        _ => "◼".blue(),
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -109,7 +145,7 @@ fn main() -> crossterm::Result<()> {
                 .long("draw-meta")
                 .short("m")
                 .help(
-                    r#"Indicates "meta" state of cells with additional colors:
+                    r#"Indicates "meta" state of cells with additional colors (dense backend only; ignored with --backend sparse):
 - "dead" -> "alive":
     - reproduction: yellow
 - "alive" -> "dead":
@@ -123,6 +159,47 @@ fn main() -> crossterm::Result<()> {
                 .short("s")
                 .help("Shows the distribution of time per tick on program exit"),
         )
+        .arg(
+            Arg::with_name(OPTION_BACKEND)
+                .value_name("BACKEND")
+                .long("backend")
+                .short("b")
+                .possible_values(&["dense", "sparse"])
+                .help("Engine used to simulate the universe: \"dense\" is a bounded grid, \"sparse\" only tracks live cells and lets patterns expand past the terminal")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(OPTION_PATTERN_FILE)
+                .value_name("FILE")
+                .long("pattern")
+                .short("f")
+                .help("Seeds the universe from a plaintext or RLE Life pattern file instead of randomly")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(OPTION_PATTERN_TOP_LEFT)
+                .long("pattern-top-left")
+                .help("Places a --pattern file in the top-left corner of the grid instead of centering it"),
+        )
+        .arg(
+            Arg::with_name(OPTION_RULE)
+                .value_name("RULE")
+                .long("rule")
+                .short("r")
+                .help("Birth/survival rulestring in B/S notation, e.g. B3/S23 (Conway), B36/S23 (HighLife), B2/S (Seeds)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(OPTION_WRAP)
+                .long("wrap")
+                .short("w")
+                .help("Wraps neighbour lookups around the grid's edges (toroidal universe) instead of clipping at the border"),
+        )
+        .arg(
+            Arg::with_name(OPTION_HEATMAP)
+                .long("heatmap")
+                .help("Colours living cells by how long they've persisted, instead of a single colour (dense backend only; ignored with --backend sparse)"),
+        )
         .get_matches();
     let term_geom = terminal::size().unwrap();
     let config = Config {
@@ -148,13 +225,24 @@ fn main() -> crossterm::Result<()> {
             .unwrap_or(0.5),
         draw_meta_state: matches.is_present(OPTION_DRAW_META_STATE),
         statistics: matches.is_present(OPTION_STATISTICS),
+        backend: match matches.value_of(OPTION_BACKEND) {
+            Some("sparse") => Backend::Sparse,
+            _ => Backend::Dense,
+        },
+        pattern_file: matches.value_of(OPTION_PATTERN_FILE).map(PathBuf::from),
+        pattern_center: !matches.is_present(OPTION_PATTERN_TOP_LEFT),
+        rule: matches
+            .value_of(OPTION_RULE)
+            .map(|s| Rule::parse(s).expect("couldn't parse rule value"))
+            .unwrap_or_default(),
+        wrap: matches.is_present(OPTION_WRAP),
+        heatmap: matches.is_present(OPTION_HEATMAP),
     };
-
-    let mut game = Game::new(
-        config.width,
-        config.height,
-        GridInitialization::Random(config.probability),
-    );
+    if matches!(config.backend, Backend::Sparse) && (config.heatmap || config.draw_meta_state) {
+        eprintln!(
+            "warning: --heatmap/--draw-meta have no effect with --backend sparse (it tracks no per-cell age or state)"
+        );
+    }
 
     let running = Arc::new(AtomicBool::new(true));
     let r = running.clone();
@@ -176,6 +264,106 @@ fn main() -> crossterm::Result<()> {
     };
     let term_width = min(config.width, term_geom.0 as usize);
     let term_height = min(config.height, term_geom.1 as usize);
+
+    let cell_count = match config.backend {
+        Backend::Dense => run_dense(
+            &mut stdout,
+            &config,
+            term_width,
+            term_height,
+            &running,
+            &mut profiler,
+        )?,
+        Backend::Sparse => run_sparse(
+            &mut stdout,
+            &config,
+            term_width,
+            term_height,
+            &running,
+            &mut profiler,
+        )?,
+    };
+
+    stdout
+        .queue(cursor::Show)?
+        .queue(terminal::LeaveAlternateScreen)?
+        .flush()?;
+
+    if let Some(mut profiler) = profiler {
+        println!(
+            "Statistics for [grid size: {} cells] [tick delay: {}ms]",
+            cell_count,
+            config.delay.as_millis()
+        );
+        println!(
+            "{:<10} | {:<12} | {:<9} | {:<9}",
+            "percentile", "overall (ms)", "draw (ms)", "tick (ms)"
+        );
+        profiler
+            .get_distribution(vec![0.99, 0.95, 0.70, 0.5, 0.3, 0.05, 0.01])
+            .iter()
+            .for_each(|(percentile, data)| {
+                println!(
+                    "{:>10} | {:>12} | {:>9} | {:>9}",
+                    percentile * (100 as f64),
+                    data.overall,
+                    data.draw,
+                    data.tick
+                )
+            })
+    }
+    Ok(())
+}
+
+/// Polls for a playback-control keypress (pause/step/speed/reseed/quit) with
+/// a timeout of `delay` and applies it, calling `reseed` for `r`. Shared by
+/// `run_dense` and `run_sparse` so their key bindings can't drift apart.
+/// Returns whether the caller should single-step the next tick regardless
+/// of `paused`.
+fn handle_controls(
+    delay: &mut time::Duration,
+    paused: &mut bool,
+    running: &Arc<AtomicBool>,
+    mut reseed: impl FnMut(),
+) -> crossterm::Result<bool> {
+    let mut single_step = false;
+    if event::poll(*delay)? {
+        if let Event::Key(key_event) = event::read()? {
+            match key_event.code {
+                KeyCode::Char(' ') => *paused = !*paused,
+                KeyCode::Char('n') => single_step = true,
+                KeyCode::Char('+') => *delay += DELAY_STEP,
+                KeyCode::Char('-') => *delay = delay.saturating_sub(DELAY_STEP).max(MIN_DELAY),
+                KeyCode::Char('r') => reseed(),
+                KeyCode::Char('q') => running.store(false, Ordering::SeqCst),
+                _ => {}
+            }
+        }
+    }
+    Ok(single_step)
+}
+
+fn run_dense(
+    stdout: &mut std::io::Stdout,
+    config: &Config,
+    term_width: usize,
+    term_height: usize,
+    running: &Arc<AtomicBool>,
+    profiler: &mut Option<Profiler>,
+) -> crossterm::Result<usize> {
+    let init = match &config.pattern_file {
+        Some(path) => GridInitialization::FromPattern(path.clone(), config.pattern_center),
+        None => GridInitialization::Random(config.probability),
+    };
+    let mut game = Game::new(
+        config.width,
+        config.height,
+        init,
+        config.rule,
+        config.wrap,
+    );
+    let mut delay = config.delay;
+    let mut paused = false;
     'outer: loop {
         let t_start = time::SystemTime::now();
         if !running.load(Ordering::SeqCst) {
@@ -188,8 +376,10 @@ fn main() -> crossterm::Result<()> {
                 stdout.queue(cursor::MoveTo(j as u16, i as u16))?;
                 if let Some(cell) = grid.get(i * config.width + j) {
                     match cell {
-                        Cell::Alive(state) => {
-                            if config.draw_meta_state {
+                        Cell::Alive(state, age) => {
+                            if config.heatmap {
+                                stdout.queue(PrintStyledContent(heatmap_symbol(*age)))?;
+                            } else if config.draw_meta_state {
                                 match state {
                                     LivingState::Remains => {
                                         stdout.queue(PrintStyledContent("◼".white()))?;
@@ -224,11 +414,23 @@ fn main() -> crossterm::Result<()> {
             }
         }
         let d_draw = t_start.elapsed().unwrap();
+        stdout.flush()?;
+
+        let single_step = handle_controls(&mut delay, &mut paused, running, || {
+            game = Game::new(
+                config.width,
+                config.height,
+                GridInitialization::Random(config.probability),
+                config.rule,
+                config.wrap,
+            );
+        })?;
+
         let t_tick = time::SystemTime::now();
-        game.tick();
+        if !paused || single_step {
+            game.tick();
+        }
         let d_tick = t_tick.elapsed().unwrap();
-        stdout.flush()?;
-        thread::sleep(config.delay);
         if let Some(ref mut p) = profiler {
             p.add(
                 d_draw.as_millis(),
@@ -237,34 +439,79 @@ fn main() -> crossterm::Result<()> {
             );
         }
     }
+    Ok(game.get_grid().len())
+}
 
-    stdout
-        .queue(cursor::Show)?
-        .queue(terminal::LeaveAlternateScreen)?
-        .flush()?;
+/// Same render/tick loop as `run_dense`, but backed by a `SparseGame`: only
+/// live cells falling inside the terminal viewport are drawn, and the
+/// universe itself has no border to clip against.
+fn run_sparse(
+    stdout: &mut std::io::Stdout,
+    config: &Config,
+    term_width: usize,
+    term_height: usize,
+    running: &Arc<AtomicBool>,
+    profiler: &mut Option<Profiler>,
+) -> crossterm::Result<usize> {
+    let new_sparse_game = |config: &Config| match &config.pattern_file {
+        Some(path) => {
+            let cells =
+                load_sparse_pattern(path, config.width, config.height, config.pattern_center)
+                    .expect("couldn't load pattern file");
+            SparseGame::from_cells(cells, config.rule)
+        }
+        None => SparseGame::new(
+            config.width as i64,
+            config.height as i64,
+            config.probability,
+            config.rule,
+        ),
+    };
+    let mut game = new_sparse_game(config);
+    let mut delay = config.delay;
+    let mut paused = false;
+    'outer: loop {
+        let t_start = time::SystemTime::now();
+        if !running.load(Ordering::SeqCst) {
+            break 'outer;
+        }
+        stdout.queue(cursor::MoveTo(0, 0))?;
+        for i in 0..term_height {
+            for j in 0..term_width {
+                stdout.queue(cursor::MoveTo(j as u16, i as u16))?;
+                stdout.queue(PrintStyledContent(" ".black()))?;
+            }
+        }
+        for &(x, y) in game.get_cells() {
+            if x >= 0 && y >= 0 && (x as usize) < term_width && (y as usize) < term_height {
+                stdout.queue(cursor::MoveTo(x as u16, y as u16))?;
+                stdout.queue(PrintStyledContent("◼".white()))?;
+            }
+        }
+        let d_draw = t_start.elapsed().unwrap();
+        stdout.flush()?;
 
-    if let Some(mut profiler) = profiler {
-        println!(
-            "Statistics for [grid size: {} cells] [tick delay: {}ms]",
-            game.get_grid().len(),
-            config.delay.as_millis()
-        );
-        println!(
-            "{:<10} | {:<12} | {:<9} | {:<9}",
-            "percentile", "overall (ms)", "draw (ms)", "tick (ms)"
-        );
-        profiler
-            .get_distribution(vec![0.99, 0.95, 0.70, 0.5, 0.3, 0.05, 0.01])
-            .iter()
-            .for_each(|(percentile, data)| {
-                println!(
-                    "{:>10} | {:>12} | {:>9} | {:>9}",
-                    percentile * (100 as f64),
-                    data.overall,
-                    data.draw,
-                    data.tick
-                )
-            })
+        let single_step = handle_controls(&mut delay, &mut paused, running, || {
+            game = SparseGame::new(
+                config.width as i64,
+                config.height as i64,
+                config.probability,
+                config.rule,
+            );
+        })?;
+
+        let t_tick = time::SystemTime::now();
+        if !paused || single_step {
+            game.tick();
+        }
+        let d_tick = t_tick.elapsed().unwrap();
+        if let Some(ref mut p) = profiler {
+            p.add(
+                d_draw.as_millis(),
+                d_tick.as_millis(),
+                t_start.elapsed().unwrap().as_millis(),
+            );
+        }
     }
-    Ok(())
+    Ok(config.width * config.height)
 }
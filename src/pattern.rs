@@ -0,0 +1,269 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A pattern decoded from a Life file, before it has been placed onto a
+/// concrete grid: its own dimensions plus the coordinates of its live cells.
+pub struct Pattern {
+    width: usize,
+    height: usize,
+    live_cells: Vec<(usize, usize)>,
+}
+
+#[derive(Debug)]
+pub enum PatternError {
+    Io(std::io::Error),
+    InvalidRle(String),
+    TooLarge {
+        pattern_width: usize,
+        pattern_height: usize,
+        grid_width: usize,
+        grid_height: usize,
+    },
+}
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternError::Io(e) => write!(f, "couldn't read pattern file: {}", e),
+            PatternError::InvalidRle(reason) => write!(f, "invalid RLE pattern: {}", reason),
+            PatternError::TooLarge {
+                pattern_width,
+                pattern_height,
+                grid_width,
+                grid_height,
+            } => write!(
+                f,
+                "pattern ({}x{}) doesn't fit in the grid ({}x{})",
+                pattern_width, pattern_height, grid_width, grid_height
+            ),
+        }
+    }
+}
+impl std::error::Error for PatternError {}
+impl From<std::io::Error> for PatternError {
+    fn from(e: std::io::Error) -> Self {
+        PatternError::Io(e)
+    }
+}
+
+fn parse_plaintext(content: &str) -> Pattern {
+    let mut live_cells = Vec::new();
+    let mut width = 0;
+    let mut height = 0;
+    for line in content.lines() {
+        if line.starts_with('!') {
+            continue;
+        }
+        width = width.max(line.len());
+        for (x, c) in line.chars().enumerate() {
+            if c != '.' && c != ' ' {
+                live_cells.push((x, height));
+            }
+        }
+        height += 1;
+    }
+    Pattern {
+        width,
+        height,
+        live_cells,
+    }
+}
+
+fn parse_rle(content: &str) -> Result<Pattern, PatternError> {
+    let mut width = 0;
+    let mut height = 0;
+    let mut header_seen = false;
+    let mut live_cells = Vec::new();
+    let mut x: usize = 0;
+    let mut y: usize = 0;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !header_seen {
+            for field in line.split(',') {
+                let mut parts = field.splitn(2, '=');
+                let key = parts.next().unwrap_or("").trim();
+                let value = parts.next().unwrap_or("").trim();
+                match key {
+                    "x" => {
+                        width = value
+                            .parse()
+                            .map_err(|_| PatternError::InvalidRle(format!("bad x value: {}", value)))?
+                    }
+                    "y" => {
+                        height = value
+                            .parse()
+                            .map_err(|_| PatternError::InvalidRle(format!("bad y value: {}", value)))?
+                    }
+                    _ => {}
+                }
+            }
+            header_seen = true;
+            continue;
+        }
+        let mut count: usize = 0;
+        let mut has_count = false;
+        for c in line.chars() {
+            if c.is_ascii_digit() {
+                count = count * 10 + c.to_digit(10).unwrap() as usize;
+                has_count = true;
+                continue;
+            }
+            let run = if has_count { count } else { 1 };
+            match c {
+                'b' => x += run,
+                'o' => {
+                    for _ in 0..run {
+                        live_cells.push((x, y));
+                        x += 1;
+                    }
+                }
+                '$' => {
+                    y += run;
+                    x = 0;
+                }
+                '!' => break,
+                _ => {
+                    return Err(PatternError::InvalidRle(format!(
+                        "unexpected tag '{}'",
+                        c
+                    )))
+                }
+            }
+            count = 0;
+            has_count = false;
+        }
+    }
+    // The header's `x`/`y` are only a hint; a malformed or mismatched file
+    // can decode a body larger than it declares, so size the pattern off
+    // the actual decoded extent rather than trusting the header alone.
+    let decoded_width = live_cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0);
+    let decoded_height = live_cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0);
+    Ok(Pattern {
+        width: width.max(decoded_width),
+        height: height.max(decoded_height),
+        live_cells,
+    })
+}
+
+fn parse(content: &str, path: &Path) -> Result<Pattern, PatternError> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("rle") => parse_rle(content),
+        _ => Ok(parse_plaintext(content)),
+    }
+}
+
+/// Decodes the pattern at `path` and places it within `width x height`,
+/// erroring if it doesn't fit. The pattern is centered when `center` is
+/// `true`, otherwise placed in the top-left corner. Shared by both the
+/// dense (`load_into_grid`) and sparse (`load_cells`) loaders.
+fn decode_and_place(
+    path: &Path,
+    width: usize,
+    height: usize,
+    center: bool,
+) -> Result<Vec<(usize, usize)>, PatternError> {
+    let content = fs::read_to_string(path)?;
+    let pattern = parse(&content, path)?;
+    if pattern.width > width || pattern.height > height {
+        return Err(PatternError::TooLarge {
+            pattern_width: pattern.width,
+            pattern_height: pattern.height,
+            grid_width: width,
+            grid_height: height,
+        });
+    }
+    let (offset_x, offset_y) = if center {
+        ((width - pattern.width) / 2, (height - pattern.height) / 2)
+    } else {
+        (0, 0)
+    };
+    Ok(pattern
+        .live_cells
+        .into_iter()
+        .map(|(x, y)| (x + offset_x, y + offset_y))
+        .collect())
+}
+
+/// Decodes the pattern at `path` (plaintext or RLE, picked by extension) and
+/// places its live cells into a `width x height` grid of `Cell`s.
+pub fn load_into_grid(
+    path: &Path,
+    width: usize,
+    height: usize,
+    center: bool,
+) -> Result<Vec<crate::Cell>, PatternError> {
+    let live_cells = decode_and_place(path, width, height, center)?;
+    let mut grid: Vec<crate::Cell> = (0..width * height)
+        .map(|_| crate::Cell::Dead(crate::DeathState::Remains))
+        .collect();
+    for (x, y) in live_cells {
+        grid[y * width + x] = crate::Cell::Alive(crate::LivingState::Remains, 0);
+    }
+    Ok(grid)
+}
+
+/// Decodes the pattern at `path` into the set of live coordinates a
+/// `SparseGame` works with, placed within a `width x height` viewport.
+pub fn load_cells(
+    path: &Path,
+    width: usize,
+    height: usize,
+    center: bool,
+) -> Result<crate::SparseGrid, PatternError> {
+    let live_cells = decode_and_place(path, width, height, center)?;
+    Ok(live_cells
+        .into_iter()
+        .map(|(x, y)| (x as i64, y as i64))
+        .collect())
+}
+
+#[test]
+fn test_parse_plaintext() {
+    let content = ".O.\nO.O\n.O.\n";
+    let pattern = parse_plaintext(content);
+    assert_eq!(pattern.width, 3);
+    assert_eq!(pattern.height, 3);
+    let mut cells = pattern.live_cells.clone();
+    cells.sort();
+    assert_eq!(cells, vec![(0, 1), (1, 0), (1, 2), (2, 1)]);
+}
+
+#[test]
+fn test_parse_rle_glider() {
+    let content = "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+    let pattern = parse_rle(content).unwrap();
+    assert_eq!(pattern.width, 3);
+    assert_eq!(pattern.height, 3);
+    let mut cells = pattern.live_cells.clone();
+    cells.sort();
+    assert_eq!(cells, vec![(0, 2), (1, 0), (1, 2), (2, 1), (2, 2)]);
+}
+
+#[test]
+fn test_load_into_grid_top_left_placement() {
+    let mut path = std::env::temp_dir();
+    path.push("game_of_life_test_top_left.cells");
+    fs::write(&path, "O\n").unwrap();
+    let grid = load_into_grid(&path, 3, 3, false).unwrap();
+    fs::remove_file(&path).ok();
+    assert_eq!(
+        grid[0],
+        crate::Cell::Alive(crate::LivingState::Remains, 0)
+    );
+    assert!(grid[1..]
+        .iter()
+        .all(|cell| matches!(cell, crate::Cell::Dead(_))));
+}
+
+#[test]
+fn test_load_into_grid_rejects_pattern_larger_than_header_claims() {
+    let mut path = std::env::temp_dir();
+    path.push("game_of_life_test_oversized.rle");
+    fs::write(&path, "x = 2, y = 2, rule = B3/S23\n20o!\n").unwrap();
+    let result = load_into_grid(&path, 10, 10, true);
+    fs::remove_file(&path).ok();
+    assert!(matches!(result, Err(PatternError::TooLarge { .. })));
+}
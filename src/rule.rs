@@ -0,0 +1,106 @@
+use std::fmt;
+
+use crate::DeathState;
+
+/// An outer-totalistic rule in B/S notation: `birth[n]`/`survive[n]` say
+/// whether a dead/live cell with `n` live neighbours is born/survives.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rule {
+    birth: [bool; 9],
+    survive: [bool; 9],
+}
+
+#[derive(Debug)]
+pub struct RuleParseError(String);
+impl fmt::Display for RuleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid rulestring: {}", self.0)
+    }
+}
+impl std::error::Error for RuleParseError {}
+
+impl Rule {
+    pub fn new(birth: [bool; 9], survive: [bool; 9]) -> Self {
+        Self { birth, survive }
+    }
+    /// Conway's Game of Life: `B3/S23`.
+    pub fn conway() -> Self {
+        Self::parse("B3/S23").unwrap()
+    }
+    /// Parses a rulestring such as `B3/S23`, `B36/S23` (HighLife) or `B2/S`
+    /// (Seeds) into its birth/survival neighbour-count sets.
+    pub fn parse(s: &str) -> Result<Self, RuleParseError> {
+        let mut parts = s.splitn(2, '/');
+        let b_part = parts.next().unwrap_or("");
+        let s_part = parts.next().unwrap_or("");
+        let leads_with = |part: &str, c: char| part.chars().next().map(|h| h.eq_ignore_ascii_case(&c));
+        if leads_with(b_part, 'B') != Some(true) || leads_with(s_part, 'S') != Some(true) {
+            return Err(RuleParseError(format!(
+                "expected \"B<digits>/S<digits>\", got \"{}\"",
+                s
+            )));
+        }
+        let birth = Self::parse_digits(&b_part[1..])?;
+        let survive = Self::parse_digits(&s_part[1..])?;
+        Ok(Self { birth, survive })
+    }
+    fn parse_digits(digits: &str) -> Result<[bool; 9], RuleParseError> {
+        let mut set = [false; 9];
+        for c in digits.chars() {
+            let n = c
+                .to_digit(10)
+                .ok_or_else(|| RuleParseError(format!("expected a digit, got '{}'", c)))?
+                as usize;
+            if n > 8 {
+                return Err(RuleParseError(format!(
+                    "neighbour count {} is out of range (0-8)",
+                    n
+                )));
+            }
+            set[n] = true;
+        }
+        Ok(set)
+    }
+    pub(crate) fn will_be_born(&self, neighbour_count: u8) -> bool {
+        self.birth[neighbour_count as usize]
+    }
+    pub(crate) fn will_survive(&self, neighbour_count: u8) -> bool {
+        self.survive[neighbour_count as usize]
+    }
+    /// Classifies why a live cell with `neighbour_count` neighbours fails to
+    /// survive, by comparing it against the rule's surviving range. This is
+    /// purely cosmetic (it only affects which `DeathState` a cell is drawn
+    /// with, not the simulation itself), and for rulestrings with
+    /// non-contiguous survival digits (e.g. `S13`) it can mislabel a death
+    /// as over- rather than underpopulation or vice versa.
+    pub(crate) fn death_state(&self, neighbour_count: u8) -> DeathState {
+        match self.survive.iter().position(|&s| s) {
+            Some(min) if (neighbour_count as usize) < min => DeathState::Underpopulation,
+            Some(_) => DeathState::Overpopulation,
+            None => DeathState::Underpopulation,
+        }
+    }
+}
+impl Default for Rule {
+    fn default() -> Self {
+        Self::conway()
+    }
+}
+
+#[test]
+fn test_rule_parse_conway() {
+    let rule = Rule::parse("B3/S23").unwrap();
+    assert_eq!(rule, Rule::conway());
+    assert!(rule.will_be_born(3));
+    assert!(!rule.will_be_born(2));
+    assert!(rule.will_survive(2));
+    assert!(rule.will_survive(3));
+    assert!(!rule.will_survive(4));
+}
+
+#[test]
+fn test_rule_parse_seeds() {
+    let rule = Rule::parse("B2/S").unwrap();
+    assert!(rule.will_be_born(2));
+    assert!(!rule.will_survive(2));
+}
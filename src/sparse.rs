@@ -0,0 +1,75 @@
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+use crate::Rule;
+
+pub type SparseGrid = HashSet<(i64, i64)>;
+
+/// A sparse, unbounded alternative to `Game` that only tracks live cells.
+///
+/// Because the universe has no fixed `width`/`height`, patterns such as
+/// gliders can travel indefinitely instead of being clipped at the grid
+/// border. `tick` only does work proportional to the number of live cells,
+/// rather than `width * height`.
+pub struct SparseGame {
+    cells: SparseGrid,
+    rule: Rule,
+}
+impl SparseGame {
+    fn init_rand(width: i64, height: i64, probability: f64) -> SparseGrid {
+        (0..width)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .filter(|_| rand::thread_rng().gen_bool(probability))
+            .collect()
+    }
+    fn live_neighbour_counts(&self) -> HashMap<(i64, i64), u8> {
+        let mut counts = HashMap::new();
+        for &(x, y) in &self.cells {
+            for dx in -1..=1i64 {
+                for dy in -1..=1i64 {
+                    if dx == 0 && dy == 0 {
+                        continue;
+                    }
+                    *counts.entry((x + dx, y + dy)).or_insert(0) += 1;
+                }
+            }
+        }
+        counts
+    }
+    pub fn new(width: i64, height: i64, probability: f64, rule: Rule) -> Self {
+        Self {
+            cells: Self::init_rand(width, height, probability),
+            rule,
+        }
+    }
+    pub fn from_cells(cells: SparseGrid, rule: Rule) -> Self {
+        Self { cells, rule }
+    }
+    pub fn get_cells(&self) -> &SparseGrid {
+        &self.cells
+    }
+    pub fn tick(&mut self) {
+        let counts = self.live_neighbour_counts();
+        let rule = self.rule;
+        self.cells = counts
+            .into_iter()
+            .filter(|&(pos, count)| {
+                if self.cells.contains(&pos) {
+                    rule.will_survive(count)
+                } else {
+                    rule.will_be_born(count)
+                }
+            })
+            .map(|(pos, _)| pos)
+            .collect();
+    }
+}
+
+#[test]
+fn test_sparse_game_blinker() {
+    let start: SparseGrid = [(0, 0), (1, 0), (2, 0)].iter().copied().collect();
+    let next: SparseGrid = [(1, -1), (1, 0), (1, 1)].iter().copied().collect();
+    let mut game = SparseGame::from_cells(start, Rule::conway());
+    game.tick();
+    assert_eq!(&next, game.get_cells());
+}